@@ -28,6 +28,31 @@ fn parse_char_into_u8(src: &str) -> Result<u8> {
     Ok(src.as_bytes()[0])
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutFormat {
+    Csv,
+    Tsv,
+    Json,
+    Ndjson,
+}
+
+impl std::str::FromStr for OutFormat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "csv" => Ok(OutFormat::Csv),
+            "tsv" => Ok(OutFormat::Tsv),
+            "json" => Ok(OutFormat::Json),
+            "ndjson" => Ok(OutFormat::Ndjson),
+            _ => Err(anyhow!(
+                "unknown --out_format \"{}\", expected csv, tsv, json, or ndjson",
+                s
+            )),
+        }
+    }
+}
+
 #[derive(StructOpt, Debug, Clone)]
 #[structopt(
 global_settings(& [structopt::clap::AppSettings::ColoredHelp, structopt::clap::AppSettings::VersionlessSubcommands, structopt::clap::AppSettings::DeriveDisplayOrder]),
@@ -54,6 +79,11 @@ pub struct CliCfg {
 
     #[structopt(short = "d", name = "open_db", parse(from_os_str))]
     /// existing database to import into
+    ///
+    /// When combined with --memory this also doubles as the restore path:
+    /// an existing file here is restored into memory before the import
+    /// runs. Use --save if you want the in-memory database persisted
+    /// somewhere once the import and --sqls are done.
     pub db_file: PathBuf,
 
     #[structopt(short = "v", parse(from_occurrences))]
@@ -100,14 +130,74 @@ pub struct CliCfg {
     /// Run 1 or more sql after the import.  Good especially for memory based DBs.
     pub sqls: Vec<String>,
 
+    #[structopt(long = "load-extension", parse(from_os_str))]
+    /// Load a SQLite extension (repeatable) before running --sqls
+    pub load_extension: Vec<PathBuf>,
+
+    #[structopt(long = "batch_rows", default_value("500"))]
+    /// Number of CSV rows grouped into a single multi-row INSERT statement
+    pub batch_rows: u64,
+
+    #[structopt(long = "date_format")]
+    /// strftime format to try when inferring/normalizing date-like columns
+    ///
+    /// Overrides the built-in ISO-8601 (YYYY-MM-DD, YYYY-MM-DD HH:MM:SS)
+    /// and epoch-seconds detection, for formats like "%d/%m/%Y".
+    pub date_format: Option<String>,
+
+    #[structopt(long = "infer_epoch")]
+    /// Allow plain all-digit columns to be inferred as epoch-second timestamps
+    ///
+    /// Off by default: a column of large integers (ids, phone numbers,
+    /// account numbers) looks identical to epoch seconds, so this is
+    /// opt-in rather than silently reinterpreting such a column as a date.
+    pub infer_epoch: bool,
+
     #[structopt(long = "out_delimiter", default_value(","))]
-    /// Run 1 or more sql after the import.  Good especially for memory based DBs.
+    /// Field delimiter used when --out_format is csv
     pub out_delimiter: String,
 
+    #[structopt(long = "out_format", default_value("csv"))]
+    /// Output format for --sqls results: csv, tsv, json, or ndjson
+    pub out_format: OutFormat,
+
     #[structopt(long = "memory")]
-    /// Create the database in memory - note use with --sqls as it will disappear
+    /// Create the database in memory for speed
+    ///
+    /// If the file given by -d already exists it is restored into memory
+    /// first. The in-memory database is discarded once the import and
+    /// --sqls finish unless --save is also given: use --sqls beforehand
+    /// to extract any results you need, or --save to keep the database
+    /// itself.
     pub memory: bool,
 
+    #[structopt(long = "save", parse(from_os_str))]
+    /// Back up the database to this file once the import and --sqls finish
+    ///
+    /// Mainly useful with --memory, whose database otherwise disappears
+    /// when csv2lite exits; without --save nothing is written back to -d.
+    pub save: Option<PathBuf>,
+
+    #[structopt(long = "infer_rows", default_value("0"))]
+    /// Number of rows to sample when inferring column types (INTEGER/REAL/TEXT)
+    ///
+    /// Zero reuses the same sample window as --sanity_sample.  Has no
+    /// effect when --all_text is set.
+    pub infer_rows: u64,
+
+    #[structopt(long = "all_text")]
+    /// Disable type inference and stamp every column TEXT, like before
+    pub all_text: bool,
+
+    #[structopt(long = "virtual")]
+    /// Query the csv files in place instead of importing them
+    ///
+    /// Registers each input file as a csvtab virtual table named via
+    /// filere/tablename and skips the insert loop entirely.  Meant to be
+    /// used together with --sqls to run ad-hoc SQL over large files
+    /// without paying the cost of a full import.
+    pub virtual_mode: bool,
+
 }
 
 fn get_cli() -> anyhow::Result<CliCfg> {
@@ -145,6 +235,14 @@ fn get_cli() -> anyhow::Result<CliCfg> {
         if let Some(file_re) = &ccfg.file_re {
             let re = Regex::new(file_re)?;
         }
+
+        if ccfg.out_format == OutFormat::Csv && ccfg.out_delimiter.len() != 1 {
+            return Err(anyhow!(
+                "--out_delimiter must be exactly one byte, got \"{}\"",
+                &ccfg.out_delimiter
+            ));
+        }
+
         trace!("Cli cfg: {:#?}", ccfg);
         //Logger::init(TermLogger::new(lvl, Config::default(), TerminalMode::Stderr).unwrap());
         ccfg
@@ -211,6 +309,24 @@ struct Field {
     db_type: String,
 }
 
+// every loaded table carries this extra column recording the file each row
+// was loaded from, so multi-file loads (-f a.csv -f b.csv) keep per-row
+// provenance instead of just a per-table one.
+const SOURCE_FILE_COLUMN: &str = "_source_file";
+
+// appends SOURCE_FILE_COLUMN to a freshly detected file schema so it's
+// present both in the CREATE TABLE (new table) and in the table/file schema
+// comparison (existing table) paths in load_file.
+fn append_source_file_column(mut f_sch: Vec<Field>) -> Vec<Field> {
+    let pos = f_sch.len() as u32;
+    f_sch.push(Field {
+        pos,
+        name: SOURCE_FILE_COLUMN.to_string(),
+        db_type: "TEXT".to_string(),
+    });
+    f_sch
+}
+
 fn schema(cfg: &CliCfg, conn: &Connection, tablename: &str) -> Result<Vec<Field>> {
     let sql = if cfg.overwrite_tables {
         format!("drop table {};", &tablename)
@@ -253,54 +369,202 @@ fn import_csv() -> Result<()> {
 
     let conn = if CLI.memory {
         warn!("opening in memory only");
-        Connection::open_in_memory()?
+        let mut mem_conn = Connection::open_in_memory()?;
+        if CLI.db_file.exists() {
+            restore_from_file(&mut mem_conn, &CLI.db_file)?;
+        }
+        mem_conn
     } else {
         Connection::open(&CLI.db_file)?
     };
 
     trace!("conn created starting loads");
-    for pathbuf in &CLI.files {
-        load_file(&CLI, &conn, &pathbuf)?;
+    if CLI.virtual_mode {
+        for pathbuf in &CLI.files {
+            register_virtual_table(&conn, &pathbuf)?;
+        }
+    } else {
+        for pathbuf in &CLI.files {
+            load_file(&CLI, &conn, &pathbuf)?;
+        }
     }
 
+    load_extensions(&conn)?;
+    register_scalar_functions(&conn)?;
     run_post_sqls(&conn)?;
+
+    if let Some(save_path) = &CLI.save {
+        save_to_file(&conn, save_path)?;
+    }
+
+    Ok(())
+}
+
+fn load_extensions(conn: &Connection) -> Result<()> {
+    if CLI.load_extension.is_empty() {
+        return Ok(());
+    }
+    let _guard = rusqlite::LoadExtensionGuard::new(conn)?;
+    for ext in &CLI.load_extension {
+        info!("loading sqlite extension: {}", ext.display());
+        conn.load_extension(ext, None)?;
+    }
+    Ok(())
+}
+
+fn register_scalar_functions(conn: &Connection) -> Result<()> {
+    use rusqlite::functions::FunctionFlags;
+    use std::cell::RefCell;
+    use std::collections::HashMap;
+
+    let flags = FunctionFlags::SQLITE_UTF8 | FunctionFlags::SQLITE_DETERMINISTIC;
+
+    // SQLite translates "value REGEXP pattern" into regexp(pattern, value).
+    // The pattern is almost always the same literal across every row of a
+    // query, so compiled Regexes are cached by pattern string instead of
+    // being rebuilt on every call.
+    let regex_cache: RefCell<HashMap<String, Regex>> = RefCell::new(HashMap::new());
+    conn.create_scalar_function("regexp", 2, flags, move |ctx| {
+        let pattern: String = ctx.get(0)?;
+        let text: String = ctx.get(1)?;
+        let mut cache = regex_cache.borrow_mut();
+        if !cache.contains_key(&pattern) {
+            let re = Regex::new(&pattern)
+                .map_err(|e| rusqlite::Error::UserFunctionError(Box::new(e)))?;
+            cache.insert(pattern.clone(), re);
+        }
+        Ok(cache[&pattern].is_match(&text))
+    })?;
+
+    // basename(path) extracts the last path component from a path string
+    // argument. Every loaded table carries a SOURCE_FILE_COLUMN recording
+    // the file each row came from (see write_to_db), so
+    // "select basename(<source_file_column>) from tbl" is how a row's
+    // originating file name is recovered.
+    conn.create_scalar_function("basename", 1, flags, |ctx| {
+        let path: String = ctx.get(0)?;
+        Ok(PathBuf::from(path)
+            .file_name()
+            .map(|f| f.to_string_lossy().into_owned())
+            .unwrap_or_default())
+    })?;
+
+    Ok(())
+}
+
+fn restore_from_file(mem_conn: &mut Connection, path: &PathBuf) -> Result<()> {
+    warn!("restoring in-memory database from {}", path.display());
+    let file_conn = Connection::open(path)?;
+    let backup = rusqlite::backup::Backup::new(&file_conn, mem_conn)?;
+    backup.run_to_completion(100, std::time::Duration::from_millis(0), None)?;
+    Ok(())
+}
+
+fn save_to_file(mem_conn: &Connection, path: &PathBuf) -> Result<()> {
+    warn!("persisting in-memory database to {}", path.display());
+    let mut file_conn = Connection::open(path)?;
+    let backup = rusqlite::backup::Backup::new(mem_conn, &mut file_conn)?;
+    backup.run_to_completion(100, std::time::Duration::from_millis(0), None)?;
     Ok(())
 }
 
-fn val_append(s: &mut String, val: Value) {
+fn register_virtual_table(conn: &Connection, pathbuf: &PathBuf) -> Result<()> {
+    let tablename = get_table_name(&pathbuf)?;
+    warn!(
+        "registering virtual table: {} for file: {}",
+        &tablename,
+        &pathbuf.display()
+    );
+
+    rusqlite::vtab::csvtab::load_module(&conn)?;
+
+    let filename = pathbuf.display().to_string().replace('\'', "''");
+    let sql = format!(
+        "create virtual table [{}] using csv(filename='{}', header={}, delimiter='{}');",
+        &tablename,
+        &filename,
+        if CLI.headeron { "yes" } else { "no" },
+        CLI.field_sep as char,
+    );
+    info!("Executing create virtual table sql: {}", &sql);
+
+    conn.execute(sql.as_str(), NO_PARAMS)?;
+
+    Ok(())
+}
+
+fn val_to_string(val: Value) -> String {
     match val {
-        Value::Null => s.push_str("NULL"),
-        Value::Integer(v) => s.push_str(&format!("{}", v)),
-        Value::Text(v) => s.push_str(&format!("{}", v)),
-        Value::Blob(v) => s.push_str(&format!("..BLOB..")),
-        Value::Real(v) => s.push_str(&format!("{}", v)),
+        Value::Null => String::new(),
+        Value::Integer(v) => v.to_string(),
+        Value::Real(v) => v.to_string(),
+        Value::Text(v) => v,
+        Value::Blob(v) => base64::encode(&v),
+    }
+}
+
+fn val_to_json(val: Value) -> serde_json::Value {
+    match val {
+        Value::Null => serde_json::Value::Null,
+        Value::Integer(v) => serde_json::Value::from(v),
+        Value::Real(v) => serde_json::Value::from(v),
+        Value::Text(v) => serde_json::Value::from(v),
+        Value::Blob(v) => serde_json::Value::from(base64::encode(&v)),
     }
 }
 
 fn run_post_sqls(conn: &Connection) -> Result<()> {
     for sql in CLI.sqls.iter() {
         let mut stmt = conn.prepare(&sql)?;
-        let mut sb = String::new();
-
-        // todo!("this whole thing is silly and should be redone more elegantly");
-        for i in 0 .. stmt.column_count()-1 {
-            print!("{}{}",stmt.column_names()[i], &CLI.out_delimiter);
-        }
-        println!("{}", stmt.column_names()[stmt.column_count()-1]);
-
-        let mut rows = stmt.query(NO_PARAMS)?;
-        while let Some(row) = rows.next()? {
-            let row: &Row = row;
-            for i in 0 .. row.column_count()-1 {
-                let x: Value = row.get(i)?;
-                val_append(&mut sb, x);
-                sb.push_str(&CLI.out_delimiter);
+        let col_names: Vec<String> = stmt
+            .column_names()
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+
+        match CLI.out_format {
+            OutFormat::Json | OutFormat::Ndjson => {
+                let mut rows_json = Vec::new();
+                let mut rows = stmt.query(NO_PARAMS)?;
+                while let Some(row) = rows.next()? {
+                    let row: &Row = row;
+                    let mut obj = serde_json::Map::new();
+                    for (i, name) in col_names.iter().enumerate() {
+                        let x: Value = row.get(i)?;
+                        obj.insert(name.clone(), val_to_json(x));
+                    }
+                    if CLI.out_format == OutFormat::Ndjson {
+                        println!("{}", serde_json::Value::Object(obj));
+                    } else {
+                        rows_json.push(serde_json::Value::Object(obj));
+                    }
+                }
+                if CLI.out_format == OutFormat::Json {
+                    println!("{}", serde_json::Value::Array(rows_json));
+                }
             }
-            {
-                let x: Value = row.get(row.column_count()-1)?;
-                val_append(&mut sb, x);
-                println!("{}", &sb);
-                sb.clear();
+            OutFormat::Csv | OutFormat::Tsv => {
+                let delimiter = if CLI.out_format == OutFormat::Tsv {
+                    b'\t'
+                } else {
+                    CLI.out_delimiter.as_bytes()[0]
+                };
+                let mut wtr = csv::WriterBuilder::new()
+                    .delimiter(delimiter)
+                    .from_writer(std::io::stdout());
+                wtr.write_record(&col_names)?;
+
+                let mut rows = stmt.query(NO_PARAMS)?;
+                while let Some(row) = rows.next()? {
+                    let row: &Row = row;
+                    let mut rec: Vec<String> = Vec::with_capacity(col_names.len());
+                    for i in 0..col_names.len() {
+                        let x: Value = row.get(i)?;
+                        rec.push(val_to_string(x));
+                    }
+                    wtr.write_record(&rec)?;
+                }
+                wtr.flush()?;
             }
         }
     }
@@ -321,16 +585,22 @@ fn load_file(cfg: &CliCfg, conn: &Connection, pathbuf: &PathBuf) -> Result<()> {
     let table_schema = schema(&cfg, &conn, &tablename)?;
     trace!("table schema: {:#?}", &table_schema);
 
-    let file_schema = detect_file_schema(pathbuf)?;
+    let file_schema = append_source_file_column(detect_file_schema(pathbuf)?);
     trace!(
         "file schema, file: {}, schema: {:#?}",
         &pathbuf.display(),
         &file_schema
     );
 
-    if table_schema.len() == 0 {
+    // schema actually used to load this file: the freshly inferred one for a
+    // new table, or the table's already-established one for an existing
+    // table, so that loading a second file with -f (each inferred
+    // independently) can't change a column's affinity out from under the
+    // first file's CREATE TABLE.
+    let load_schema = if table_schema.len() == 0 {
         // create table
         create_table(&conn, &tablename, &file_schema)?;
+        file_schema
     } else {
         //
         // compare db schema vs file schema
@@ -349,27 +619,162 @@ fn load_file(cfg: &CliCfg, conn: &Connection, pathbuf: &PathBuf) -> Result<()> {
                     &pathbuf.display()
                 ));
             } else if tmp.0.db_type != tmp.1.db_type {
-                return Err(anyhow!(
-                    "Schema diff in type: table field {} vs file field {}  table: {}  file: {}",
+                // each file in a multi-file -f load infers its own affinity
+                // for the same column; rather than hard-error on the
+                // (common) case where files disagree, keep the affinity the
+                // table was already created with. Use --all_text up front
+                // if the files are heterogeneous enough that this matters.
+                warn!(
+                    "Schema diff in type, keeping table's existing type: table field {} is {} but file {} inferred {}  table: {}  file: {}",
+                    &tmp.0.name,
                     &tmp.0.db_type,
+                    &tmp.1.name,
                     &tmp.1.db_type,
                     &tablename,
                     &pathbuf.display()
-                ));
+                );
             }
         }
-    }
+        table_schema
+    };
 
     //
     // load data
     //
-    let (rows, fields) = write_to_db(&conn, &pathbuf, &tablename, &file_schema)?;
+    let (rows, fields) = write_to_db(&conn, &pathbuf, &tablename, &load_schema)?;
 
     warn!("Loaded {}/{} rows/fields into \"{}\" in {:.3} seconds", rows, fields, &tablename, start.elapsed().as_secs_f64());
 
     Ok(())
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum ColType {
+    Integer,
+    Real,
+    Text,
+}
+
+impl ColType {
+    fn affinity(self) -> &'static str {
+        match self {
+            ColType::Integer => "INTEGER",
+            ColType::Real => "REAL",
+            ColType::Text => "TEXT",
+        }
+    }
+}
+
+// leading-zero id-like strings ("0042") and other digit-only strings that
+// would not round-trip through i64/f64 (e.g. longer than i64 can hold) are
+// kept as TEXT rather than being widened to a numeric affinity.
+fn looks_like_leading_zero_int(s: &str) -> bool {
+    let b = s.as_bytes();
+    b.len() > 1 && b[0] == b'0' && b.iter().all(u8::is_ascii_digit)
+}
+
+// all-digit strings (optionally signed) of 19 digits or more - i64::MAX
+// itself has 19 - may not round-trip through i64, and parsing them as f64
+// instead would silently lose precision, so they're kept TEXT rather than
+// widened to REAL, including 19-digit values that would fit in an i64.
+fn is_overlong_digit_string(s: &str) -> bool {
+    let digits = s.strip_prefix('-').unwrap_or(s);
+    digits.len() >= 19 && !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit())
+}
+
+fn infer_value_type(s: &str) -> ColType {
+    if looks_like_leading_zero_int(s) || is_overlong_digit_string(s) {
+        ColType::Text
+    } else if s.parse::<i64>().is_ok() {
+        ColType::Integer
+    } else if s.parse::<f64>().is_ok() {
+        ColType::Real
+    } else {
+        ColType::Text
+    }
+}
+
+// widens col_types in place; empty fields are treated as NULL and never
+// force a column's type.
+fn accumulate_col_types(record: &StringRecord, col_types: &mut Vec<Option<ColType>>) {
+    for (i, val) in record.iter().enumerate() {
+        if i >= col_types.len() || val.is_empty() {
+            continue;
+        }
+        let seen = infer_value_type(val);
+        col_types[i] = Some(match col_types[i] {
+            None => seen,
+            Some(cur) => std::cmp::max(cur, seen),
+        });
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DateKind {
+    IsoDate,
+    IsoDateTime,
+    EpochSeconds,
+    Custom,
+}
+
+// date_format/infer_epoch are threaded in explicitly (rather than read
+// from the global CLI) so this stays a pure, independently testable
+// function.
+fn detect_date_kind(s: &str, date_format: Option<&str>, infer_epoch: bool) -> Option<DateKind> {
+    if let Some(fmt) = date_format {
+        return if chrono::NaiveDateTime::parse_from_str(s, fmt).is_ok()
+            || chrono::NaiveDate::parse_from_str(s, fmt).is_ok()
+        {
+            Some(DateKind::Custom)
+        } else {
+            None
+        };
+    }
+    if chrono::NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S").is_ok() {
+        return Some(DateKind::IsoDateTime);
+    }
+    if chrono::NaiveDate::parse_from_str(s, "%Y-%m-%d").is_ok() {
+        return Some(DateKind::IsoDate);
+    }
+    // Epoch seconds are indistinguishable from a large plain integer (an
+    // id, a phone number, an account number) by value alone, so this
+    // branch only fires when the caller has explicitly opted in.
+    if infer_epoch && !looks_like_leading_zero_int(s) && s.len() >= 9 {
+        if let Ok(epoch) = s.parse::<i64>() {
+            if chrono::DateTime::from_timestamp(epoch, 0).is_some() {
+                return Some(DateKind::EpochSeconds);
+            }
+        }
+    }
+    None
+}
+
+// records each column's date_kind in place, or permanently disqualifies the
+// column on the first value that doesn't parse as a date or parses as a
+// different kind than earlier values; empty fields are treated as NULL.
+fn accumulate_date_kinds(
+    record: &StringRecord,
+    date_kind: &mut Vec<Option<DateKind>>,
+    date_failed: &mut Vec<bool>,
+    date_format: Option<&str>,
+    infer_epoch: bool,
+) {
+    for (i, val) in record.iter().enumerate() {
+        if i >= date_kind.len() || date_failed[i] || val.is_empty() {
+            continue;
+        }
+        match detect_date_kind(val, date_format, infer_epoch) {
+            Some(kind) if date_kind[i].is_none() || date_kind[i] == Some(kind) => {
+                date_kind[i] = Some(kind);
+            }
+            _ => {
+                date_failed[i] = true;
+                date_kind[i] = None;
+            }
+        }
+    }
+}
+
 fn detect_file_schema(pathbuf: &PathBuf) -> Result<Vec<Field>> {
     let mut rdr = match DecompressionReader::new(&pathbuf) {
         Ok(rdr) => rdr,
@@ -394,12 +799,31 @@ fn detect_file_schema(pathbuf: &PathBuf) -> Result<Vec<Field>> {
     let mut line_count = 0;
 
     let mut header_field_count = 0;
-
-    let sanity_sample = if CLI.headeron {
-        CLI.sanity_sample + 1
+    let mut col_types: Vec<Option<ColType>> = Vec::new();
+    let mut date_kind: Vec<Option<DateKind>> = Vec::new();
+    let mut date_failed: Vec<bool> = Vec::new();
+
+    // None means "no limit" - the documented meaning of zero for
+    // --sanity_sample, and what --infer_rows=0 reuses from it.
+    let sanity_sample: Option<u64> = if CLI.sanity_sample == 0 {
+        None
+    } else if CLI.headeron {
+        Some(CLI.sanity_sample + 1)
     } else {
-        CLI.sanity_sample
+        Some(CLI.sanity_sample)
+    };
+    let infer_rows: Option<u64> = if CLI.infer_rows == 0 {
+        sanity_sample
+    } else if CLI.headeron {
+        Some(CLI.infer_rows + 1)
+    } else {
+        Some(CLI.infer_rows)
+    };
+    let scan_limit: Option<u64> = match (sanity_sample, infer_rows) {
+        (Some(a), Some(b)) => Some(std::cmp::max(a, b)),
+        _ => None,
     };
+
     for record in rec_rdr.records() {
         let record = record?;
         line_count += 1;
@@ -410,23 +834,47 @@ fn detect_file_schema(pathbuf: &PathBuf) -> Result<Vec<Field>> {
                     let f = Field {
                         pos: i as u32,
                         name: f.to_string(),
-                        db_type: "text".to_string(),
+                        db_type: "TEXT".to_string(),
                     };
                     schema.push(f);
                 }
             }
             header_field_count = record.len();
+            col_types = vec![None; header_field_count];
+            date_kind = vec![None; header_field_count];
+            date_failed = vec![false; header_field_count];
+            if !CLI.headeron && !CLI.all_text {
+                accumulate_col_types(&record, &mut col_types);
+                accumulate_date_kinds(
+                    &record,
+                    &mut date_kind,
+                    &mut date_failed,
+                    CLI.date_format.as_deref(),
+                    CLI.infer_epoch,
+                );
+            }
         } else {
-            if line_count > sanity_sample {
-                break;
+            if let Some(n) = scan_limit {
+                if line_count > n {
+                    break;
+                }
             }
-            if !CLI.ignore_field_count || record.len() != header_field_count {
+            if sanity_sample.is_none_or(|n| line_count <= n)
+                && !CLI.ignore_field_count
+                && record.len() != header_field_count
+            {
                 return Err(anyhow!("Field count inconsistency: line: {}  field count: {}  expected field count: {}  file: {}", line_count, record.len(), header_field_count, &pathbuf.display()));
             }
-        }
-
-        if line_count > 10 {
-            break;
+            if !CLI.all_text && infer_rows.is_none_or(|n| line_count <= n) {
+                accumulate_col_types(&record, &mut col_types);
+                accumulate_date_kinds(
+                    &record,
+                    &mut date_kind,
+                    &mut date_failed,
+                    CLI.date_format.as_deref(),
+                    CLI.infer_epoch,
+                );
+            }
         }
     }
 
@@ -437,6 +885,15 @@ fn detect_file_schema(pathbuf: &PathBuf) -> Result<Vec<Field>> {
                 pathbuf.display()
             ));
         }
+        if !CLI.all_text {
+            for (i, field) in schema.iter_mut().enumerate() {
+                field.db_type = if !date_failed[i] && date_kind[i].is_some() {
+                    "DATETIME".to_string()
+                } else {
+                    col_types[i].unwrap_or(ColType::Text).affinity().to_string()
+                };
+            }
+        }
         return Ok(schema);
     } else {
         if header_field_count == 0 {
@@ -446,10 +903,17 @@ fn detect_file_schema(pathbuf: &PathBuf) -> Result<Vec<Field>> {
             ));
         } else {
             for i in 0..header_field_count {
+                let db_type = if CLI.all_text {
+                    "TEXT".to_string()
+                } else if !date_failed[i] && date_kind[i].is_some() {
+                    "DATETIME".to_string()
+                } else {
+                    col_types[i].unwrap_or(ColType::Text).affinity().to_string()
+                };
                 schema.push(Field {
                     pos: i as u32,
                     name: format!("f{}", i),
-                    db_type: "text".to_string(),
+                    db_type,
                 });
             }
         }
@@ -480,6 +944,72 @@ impl StringRecordParamed {
 
 
 
+fn set_fast_load_pragmas(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "pragma synchronous = off; \
+         pragma journal_mode = memory; \
+         pragma cache_size = -200000;",
+    )?;
+    Ok(())
+}
+
+// SQLite rejects a statement with more than SQLITE_MAX_VARIABLE_NUMBER bound
+// parameters, so a wide table can't always use the full requested batch
+// size: clamp so rows * ncols stays under that limit rather than erroring
+// out on an otherwise-valid file.
+const SQLITE_MAX_VARIABLE_NUMBER: usize = 32766;
+
+fn clamp_batch_rows(requested: usize, ncols: usize) -> usize {
+    let max_rows_for_width = std::cmp::max(1, SQLITE_MAX_VARIABLE_NUMBER / std::cmp::max(1, ncols));
+    std::cmp::max(1, requested).min(max_rows_for_width)
+}
+
+fn build_batch_insert_sql(tablename: &str, f_sch: &Vec<Field>, n_rows: usize) -> String {
+    let cols = f_sch
+        .iter()
+        .map(|f| format!("[{}]", &f.name))
+        .collect::<Vec<String>>()
+        .join(", ");
+    let row_params = format!("({})", vec!["?"; f_sch.len()].join(", "));
+    let values = vec![row_params; n_rows].join(",\n");
+
+    format!("insert into {} ( {} )\nvalues {};", &tablename, cols, values)
+}
+
+// reparses a value recognized during schema detection and reformats it
+// to a canonical "YYYY-MM-DD HH:MM:SS" timestamp; values that no longer
+// parse (shouldn't happen given sampling passed) are passed through as-is
+// rather than corrupting the row.
+// date_format/infer_epoch mirror detect_date_kind's parameters exactly, so a
+// column's values are normalized the same way its type was inferred.
+fn normalize_date_value(s: &str, date_format: Option<&str>, infer_epoch: bool) -> String {
+    const CANONICAL: &str = "%Y-%m-%d %H:%M:%S";
+
+    if let Some(fmt) = date_format {
+        if let Ok(dt) = chrono::NaiveDateTime::parse_from_str(s, fmt) {
+            return dt.format(CANONICAL).to_string();
+        }
+        if let Ok(d) = chrono::NaiveDate::parse_from_str(s, fmt) {
+            return d.and_hms_opt(0, 0, 0).unwrap().format(CANONICAL).to_string();
+        }
+        return s.to_string();
+    }
+    if let Ok(dt) = chrono::NaiveDateTime::parse_from_str(s, CANONICAL) {
+        return dt.format(CANONICAL).to_string();
+    }
+    if let Ok(d) = chrono::NaiveDate::parse_from_str(s, "%Y-%m-%d") {
+        return d.and_hms_opt(0, 0, 0).unwrap().format(CANONICAL).to_string();
+    }
+    if infer_epoch && !looks_like_leading_zero_int(s) && s.len() >= 9 {
+        if let Ok(epoch) = s.parse::<i64>() {
+            if let Some(dt) = chrono::DateTime::from_timestamp(epoch, 0) {
+                return dt.naive_utc().format(CANONICAL).to_string();
+            }
+        }
+    }
+    s.to_string()
+}
+
 fn write_to_db(
     conn: &Connection,
     pathbuf: &PathBuf,
@@ -504,26 +1034,25 @@ fn write_to_db(
         .comment(CLI.comment);
     let mut rec_rdr = builder.from_reader(rdr);
     let mut line_count = 0;
-    let mut sql = format!(
-        "insert into {} ( {} ) \nvalues( {} );",
-        &tablename,
-        f_sch
-            .iter()
-            .map(|f| format!("[{}]", &f.name))
-            .collect::<Vec<String>>()
-            .join(", "),
-        f_sch
-            .iter()
-            .enumerate()
-            .map(|(i, e)| format!("?{}", i + 1))
-            .collect::<Vec<String>>()
-            .join(", ")
-    );
+
+    let batch_rows = clamp_batch_rows(CLI.batch_rows as usize, f_sch.len());
+    let sql = build_batch_insert_sql(tablename, f_sch, batch_rows);
+    let date_cols: Vec<bool> = f_sch
+        .iter()
+        .map(|f| f.db_type.eq_ignore_ascii_case("DATETIME"))
+        .collect();
+    // f_sch carries SOURCE_FILE_COLUMN as its last entry (see
+    // append_source_file_column); that value comes from pathbuf, not the
+    // CSV itself, so the CSV's own field count is one short of f_sch.len().
+    let csv_field_count = f_sch.len() - 1;
+    let source_file_value = pathbuf.display().to_string();
+
+    set_fast_load_pragmas(&conn)?;
 
     let mut stmt = conn
         .prepare(&sql)
         .with_context(|| format!("Sql used: {}", &sql))?;
-    info!("SQL for load: {}", &sql);
+    info!("SQL for load (batch of {} rows): {}", batch_rows, &sql);
     conn.execute_batch("begin transaction;")?;
 
     let mut x_complete = std::cell::Cell::new(false);
@@ -541,35 +1070,51 @@ fn write_to_db(
 
     let (mut row_count, mut field_count) = (0u64, 0u64);
     let mut record = StringRecord::new();
-    let mut param_vec:Vec<String> = Vec::with_capacity(f_sch.len());
+    let mut param_vec: Vec<String> = Vec::with_capacity(batch_rows * f_sch.len());
+    let mut rows_in_batch = 0usize;
     while rec_rdr.read_record(&mut record)? {
         line_count += 1;
         if line_count == 1 && CLI.headeron {
             // skip this line and assume it was already checked header vs schema
         } else {
-            // we know that stmt must be set by now
-            // extend any missing blanks
             if !CLI.ignore_field_count {
-                if record.len() != f_sch.len() {
-                    return Err(anyhow!("Error trying batch insert record {}:{} field expected: {}  fields found: {}", pathbuf.display(),line_count, f_sch.len(), record.len()));
+                if record.len() != csv_field_count {
+                    return Err(anyhow!("Error trying batch insert record {}:{} field expected: {}  fields found: {}", pathbuf.display(),line_count, csv_field_count, record.len()));
                 }
             }
-             
-            param_vec.clear();
-            for (i, s) in param_vec.iter_mut().enumerate() {
-                s.clear();
-                if i < record.len()  {
-                    s.push_str(&record[i]);
+
+            for (i, s) in record.iter().enumerate() {
+                if date_cols[i] && !s.is_empty() {
+                    param_vec.push(normalize_date_value(
+                        s,
+                        CLI.date_format.as_deref(),
+                        CLI.infer_epoch,
+                    ));
+                } else {
+                    param_vec.push(s.to_string());
                 }
             }
-            record.iter().for_each(|s| param_vec.push(s.to_string()));
-            stmt.execute(rusqlite::params_from_iter(&param_vec))?;
-            
+            param_vec.push(source_file_value.clone());
+            rows_in_batch += 1;
             row_count += 1;
             field_count += f_sch.len() as u64;
+
+            if rows_in_batch == batch_rows {
+                stmt.execute(rusqlite::params_from_iter(&param_vec))?;
+                param_vec.clear();
+                rows_in_batch = 0;
+            }
         }
-        // TODO:
     }
+
+    if rows_in_batch > 0 {
+        let partial_sql = build_batch_insert_sql(tablename, f_sch, rows_in_batch);
+        let mut partial_stmt = conn
+            .prepare(&partial_sql)
+            .with_context(|| format!("Sql used: {}", &partial_sql))?;
+        partial_stmt.execute(rusqlite::params_from_iter(&param_vec))?;
+    }
+
     conn.execute_batch("commit;")?;
     x_complete.set(true);
 
@@ -589,3 +1134,136 @@ fn create_table(conn: &Connection, tablename: &str, f_sch: &Vec<Field>) -> Resul
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn append_source_file_column_adds_trailing_text_field() {
+        let f_sch = vec![Field { pos: 0, name: "f0".to_string(), db_type: "INTEGER".to_string() }];
+        let f_sch = append_source_file_column(f_sch);
+        assert_eq!(f_sch.len(), 2);
+        assert_eq!(f_sch[1].name, SOURCE_FILE_COLUMN);
+        assert_eq!(f_sch[1].db_type, "TEXT");
+        assert_eq!(f_sch[1].pos, 1);
+    }
+
+    #[test]
+    fn infer_value_type_picks_narrowest_affinity() {
+        assert_eq!(infer_value_type("42"), ColType::Integer);
+        assert_eq!(infer_value_type("-42"), ColType::Integer);
+        assert_eq!(infer_value_type("3.14"), ColType::Real);
+        assert_eq!(infer_value_type("hello"), ColType::Text);
+    }
+
+    #[test]
+    fn infer_value_type_keeps_leading_zero_ids_as_text() {
+        assert_eq!(infer_value_type("0042"), ColType::Text);
+        assert_eq!(infer_value_type("0"), ColType::Integer);
+    }
+
+    #[test]
+    fn infer_value_type_keeps_overlong_digit_strings_as_text() {
+        // 20 digits: fails i64::parse, would succeed as f64 and silently
+        // lose precision if not special-cased.
+        assert_eq!(infer_value_type("12345678901234567890"), ColType::Text);
+        assert_eq!(infer_value_type("-12345678901234567890"), ColType::Text);
+    }
+
+    #[test]
+    fn accumulate_col_types_widens_across_rows() {
+        let mut col_types: Vec<Option<ColType>> = vec![None; 2];
+        accumulate_col_types(&StringRecord::from(vec!["1", "1"]), &mut col_types);
+        accumulate_col_types(&StringRecord::from(vec!["2.5", "text"]), &mut col_types);
+        assert_eq!(col_types[0], Some(ColType::Real));
+        assert_eq!(col_types[1], Some(ColType::Text));
+    }
+
+    #[test]
+    fn detect_date_kind_recognizes_iso_formats() {
+        assert_eq!(detect_date_kind("2021-01-02", None, false), Some(DateKind::IsoDate));
+        assert_eq!(
+            detect_date_kind("2021-01-02 03:04:05", None, false),
+            Some(DateKind::IsoDateTime)
+        );
+    }
+
+    #[test]
+    fn detect_date_kind_ignores_plain_integers_by_default() {
+        // a 9+ digit account/phone number must not be misread as epoch seconds
+        assert_eq!(detect_date_kind("100000000", None, false), None);
+    }
+
+    #[test]
+    fn detect_date_kind_requires_opt_in_for_epoch_seconds() {
+        assert_eq!(
+            detect_date_kind("1609459200", None, true),
+            Some(DateKind::EpochSeconds)
+        );
+        assert_eq!(detect_date_kind("1609459200", None, false), None);
+    }
+
+    #[test]
+    fn normalize_date_value_round_trips_iso_and_epoch() {
+        assert_eq!(
+            normalize_date_value("2021-01-02", None, false),
+            "2021-01-02 00:00:00"
+        );
+        assert_eq!(
+            normalize_date_value("1609459200", None, true),
+            "2021-01-01 00:00:00"
+        );
+        // without --infer_epoch, an unrecognized plain integer passes through
+        // unchanged rather than being reinterpreted as a date
+        assert_eq!(normalize_date_value("100000000", None, false), "100000000");
+    }
+
+    #[test]
+    fn clamp_batch_rows_respects_sqlite_variable_limit() {
+        assert_eq!(clamp_batch_rows(500, 4), 500);
+        // 500 rows * 80 cols = 40000 binds, over the 32766 limit
+        assert_eq!(clamp_batch_rows(500, 80), 409);
+        assert_eq!(clamp_batch_rows(500, 80) * 80, 32720);
+        assert!(clamp_batch_rows(500, 80) * 80 <= 32766);
+        // never clamps below one row, even for a pathologically wide table
+        assert_eq!(clamp_batch_rows(500, 100_000), 1);
+    }
+
+    #[test]
+    fn batch_insert_matches_single_row_inserts() -> Result<()> {
+        let f_sch = vec![
+            Field { pos: 0, name: "a".to_string(), db_type: "INTEGER".to_string() },
+            Field { pos: 1, name: "b".to_string(), db_type: "TEXT".to_string() },
+        ];
+        let rows = vec![
+            vec!["1".to_string(), "one".to_string()],
+            vec!["2".to_string(), "two".to_string()],
+            vec!["3".to_string(), "three".to_string()],
+        ];
+
+        let single_conn = Connection::open_in_memory()?;
+        create_table(&single_conn, "t", &f_sch)?;
+        for row in &rows {
+            let sql = build_batch_insert_sql("t", &f_sch, 1);
+            single_conn.execute(&sql, rusqlite::params_from_iter(row))?;
+        }
+
+        let batch_conn = Connection::open_in_memory()?;
+        create_table(&batch_conn, "t", &f_sch)?;
+        let flat: Vec<&String> = rows.iter().flatten().collect();
+        let sql = build_batch_insert_sql("t", &f_sch, rows.len());
+        batch_conn.execute(&sql, rusqlite::params_from_iter(flat))?;
+
+        let fetch = |conn: &Connection| -> Result<Vec<(i64, String)>> {
+            let mut stmt = conn.prepare("select a, b from t order by a")?;
+            let out = stmt
+                .query_map(NO_PARAMS, |row| Ok((row.get(0)?, row.get(1)?)))?
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+            Ok(out)
+        };
+        assert_eq!(fetch(&single_conn)?, fetch(&batch_conn)?);
+
+        Ok(())
+    }
+}